@@ -1,11 +1,17 @@
 #[allow(warnings)]
 mod bindings;
+mod references;
+mod symbol_index;
 
 use anyhow::bail;
 use bindings::exports::local::module::module::{
     Guest, GuestModule, Item, PrintPart, Range, ValidateError,
 };
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
 
 struct Component;
 
@@ -127,14 +133,21 @@ impl Guest for Component {
 
 struct Module {
     bytes: Vec<u8>,
+    items_cache: RefCell<Option<Rc<Vec<Item>>>>,
+    index_cache: RefCell<Option<(u64, Rc<symbol_index::SymbolIndex>)>>,
 }
 
 impl GuestModule for Module {
     fn new(init: Vec<u8>) -> Self {
-        if let Ok(std::borrow::Cow::Owned(bytes)) = wat::parse_bytes(&init) {
-            Module { bytes }
+        let bytes = if let Ok(std::borrow::Cow::Owned(bytes)) = wat::parse_bytes(&init) {
+            bytes
         } else {
-            Module { bytes: init }
+            init
+        };
+        Module {
+            bytes,
+            items_cache: RefCell::new(None),
+            index_cache: RefCell::new(None),
         }
     }
 
@@ -176,6 +189,96 @@ impl GuestModule for Module {
     }
 }
 
+impl Module {
+    /// Parses this module's items once and reuses the result for every
+    /// later call. `self.bytes` is set at construction and never mutated
+    /// afterward, so `gather_items` — the actual full rescan — only ever
+    /// needs to run the first time something calls `indexed`; that's what
+    /// makes repeated `search` calls skip "re-parsing a module" rather than
+    /// just skipping the `fst::Map` rebuild on top of a fresh re-parse.
+    fn items_cached(&self) -> anyhow::Result<Rc<Vec<Item>>> {
+        if let Some(items) = self.items_cache.borrow().as_ref() {
+            return Ok(items.clone());
+        }
+        let items = Rc::new(gather_items(&self.bytes)?);
+        *self.items_cache.borrow_mut() = Some(items.clone());
+        Ok(items)
+    }
+
+    /// Builds (or reuses) the fuzzy-search index for this module.
+    ///
+    /// `fst::Map` has no in-place mutation, so there's no way to patch an
+    /// existing index in place — instead this fingerprints every name the
+    /// index would be built from (the `display_name`s, plus any aliases)
+    /// and skips the `fst::Map` rebuild whenever that fingerprint matches
+    /// the last one, on top of `items_cached` already skipping the rescan
+    /// itself.
+    fn indexed(
+        &self,
+        alias_overrides: &HashMap<String, Vec<String>>,
+    ) -> anyhow::Result<Rc<symbol_index::SymbolIndex>> {
+        let items = self.items_cached()?;
+        let aliases = if alias_overrides.is_empty() {
+            Vec::new()
+        } else {
+            gather_item_aliases(&self.bytes, &items, alias_overrides)?
+        };
+
+        let mut hasher = DefaultHasher::new();
+        for item in &items {
+            item.display_name.hash(&mut hasher);
+        }
+        for item_aliases in &aliases {
+            item_aliases.hash(&mut hasher);
+        }
+        let fingerprint = hasher.finish();
+
+        if let Some((cached_fingerprint, index)) = self.index_cache.borrow().as_ref() {
+            if *cached_fingerprint == fingerprint {
+                return Ok(index.clone());
+            }
+        }
+
+        let index = Rc::new(if aliases.is_empty() {
+            symbol_index::SymbolIndex::build(&items)?
+        } else {
+            symbol_index::SymbolIndex::build_with_aliases(&items, &aliases)?
+        });
+        *self.index_cache.borrow_mut() = Some((fingerprint, index.clone()));
+        Ok(index)
+    }
+
+    /// Fuzzy name search over this module's items, backed by a
+    /// [`symbol_index::SymbolIndex`]. Not yet threaded through the wit
+    /// interface (`local:module/module` has no `search` method), so this is
+    /// only reachable from Rust callers embedding this crate directly until
+    /// the interface grows one — there's no `.wit` source in this repo to
+    /// add that method to, so wiring it in is left for whoever owns the
+    /// world definition.
+    pub fn search(&self, query: &str) -> anyhow::Result<Vec<symbol_index::SymbolMatch>> {
+        self.indexed(&HashMap::new())?.fuzzy_search(query)
+    }
+
+    /// Like `search`, but also matches user-supplied aliases layered on top
+    /// of the name section (see `gather_item_aliases`).
+    pub fn search_with_aliases(
+        &self,
+        query: &str,
+        alias_overrides: &HashMap<String, Vec<String>>,
+    ) -> anyhow::Result<Vec<symbol_index::SymbolMatch>> {
+        self.indexed(alias_overrides)?.fuzzy_search(query)
+    }
+
+    /// Builds the cross-reference graph (call sites, element/data segments,
+    /// ...) over this module's items for goto-definition / find-all-uses.
+    /// Like `search`, this awaits a wit-side method before it can be called
+    /// directly from the host.
+    pub fn references(&self) -> anyhow::Result<references::ReferenceGraph> {
+        let items = gather_items(&self.bytes)?;
+        references::ReferenceGraph::build(&self.bytes, &items)
+    }
+}
+
 fn convert_range(r: &std::ops::Range<usize>) -> Range {
     Range {
         start: r.start as u32,
@@ -183,6 +286,74 @@ fn convert_range(r: &std::ops::Range<usize>) -> Range {
     }
 }
 
+/// Renders an operator as its WAT mnemonic (e.g. `local.get`, `call`) so it
+/// can be used as a searchable `raw_name` for per-instruction items.
+fn operator_mnemonic(op: &wasmparser::Operator) -> String {
+    use wasmparser::Operator::*;
+
+    match op {
+        Unreachable => "unreachable".to_owned(),
+        Nop => "nop".to_owned(),
+        Block { .. } => "block".to_owned(),
+        Loop { .. } => "loop".to_owned(),
+        If { .. } => "if".to_owned(),
+        Else => "else".to_owned(),
+        End => "end".to_owned(),
+        Br { .. } => "br".to_owned(),
+        BrIf { .. } => "br_if".to_owned(),
+        BrTable { .. } => "br_table".to_owned(),
+        Return => "return".to_owned(),
+        Call { .. } => "call".to_owned(),
+        CallIndirect { .. } => "call_indirect".to_owned(),
+        Drop => "drop".to_owned(),
+        Select => "select".to_owned(),
+        LocalGet { .. } => "local.get".to_owned(),
+        LocalSet { .. } => "local.set".to_owned(),
+        LocalTee { .. } => "local.tee".to_owned(),
+        GlobalGet { .. } => "global.get".to_owned(),
+        GlobalSet { .. } => "global.set".to_owned(),
+        I32Load { .. } => "i32.load".to_owned(),
+        I64Load { .. } => "i64.load".to_owned(),
+        F32Load { .. } => "f32.load".to_owned(),
+        F64Load { .. } => "f64.load".to_owned(),
+        I32Store { .. } => "i32.store".to_owned(),
+        I64Store { .. } => "i64.store".to_owned(),
+        F32Store { .. } => "f32.store".to_owned(),
+        F64Store { .. } => "f64.store".to_owned(),
+        MemorySize { .. } => "memory.size".to_owned(),
+        MemoryGrow { .. } => "memory.grow".to_owned(),
+        I32Const { value } => format!("i32.const {value}"),
+        I64Const { value } => format!("i64.const {value}"),
+        F32Const { value } => format!("f32.const {value:?}"),
+        F64Const { value } => format!("f64.const {value:?}"),
+        I32Eqz => "i32.eqz".to_owned(),
+        I32Add => "i32.add".to_owned(),
+        I32Sub => "i32.sub".to_owned(),
+        I32Mul => "i32.mul".to_owned(),
+        I64Add => "i64.add".to_owned(),
+        I64Sub => "i64.sub".to_owned(),
+        I64Mul => "i64.mul".to_owned(),
+        RefNull { .. } => "ref.null".to_owned(),
+        RefFunc { .. } => "ref.func".to_owned(),
+        RefIsNull => "ref.is_null".to_owned(),
+        // Fall back to a lowercase, dotted form of the variant name for the
+        // long tail of operators (SIMD, threads, GC, ...) we don't special
+        // case above.
+        other => {
+            let debug = format!("{other:?}");
+            let name = debug.split(|c: char| c == ' ' || c == '{').next().unwrap_or(&debug);
+            let mut mnemonic = String::new();
+            for (i, ch) in name.chars().enumerate() {
+                if ch.is_ascii_uppercase() && i != 0 {
+                    mnemonic.push('.');
+                }
+                mnemonic.extend(ch.to_lowercase());
+            }
+            mnemonic
+        }
+    }
+}
+
 struct Alias {
     name: String,
     item_name: String,
@@ -655,6 +826,50 @@ fn gather_items(mut bytes: &[u8]) -> anyhow::Result<Vec<Item>> {
                     raw_name: format!("func {func_index}"),
                     display_name: String::new(),
                 });
+
+                let mut locals_reader = body.get_locals_reader()?;
+                if locals_reader.get_count() != 0 {
+                    let locals_start = locals_reader.original_position();
+                    for _ in 0..locals_reader.get_count() {
+                        locals_reader.read()?;
+                    }
+
+                    items.push(Item {
+                        range: Range {
+                            start: locals_start as u32,
+                            end: locals_reader.original_position() as u32,
+                        },
+                        raw_name: format!("locals"),
+                        display_name: String::new(),
+                    });
+                }
+
+                let mut op_index = 0;
+                let mut ops = locals_reader.into_operators_reader()?;
+                while !ops.eof() {
+                    let offset = ops.original_position();
+
+                    if op_index != 0 {
+                        items.last_mut().unwrap().range.end = offset as u32;
+                    }
+
+                    let op = ops.read()?;
+                    items.push(Item {
+                        range: Range {
+                            start: offset as u32,
+                            end: offset as u32,
+                        },
+                        raw_name: operator_mnemonic(&op),
+                        display_name: String::new(),
+                    });
+
+                    op_index += 1;
+                }
+
+                if op_index != 0 {
+                    items.last_mut().unwrap().range.end = body.range().end as u32;
+                }
+
                 func_index += 1;
             }
             Payload::DataCountSection { .. } => {}
@@ -692,6 +907,48 @@ fn gather_items(mut bytes: &[u8]) -> anyhow::Result<Vec<Item>> {
                 }
             }
 
+            Payload::CustomSection(reader) if reader.name() == "producers" => {
+                let range = reader.range();
+                items.push(Item {
+                    range: convert_range(&range),
+                    raw_name: format!("producers"),
+                    display_name: String::new(),
+                });
+
+                let binary_reader = BinaryReader::new(reader.data(), reader.data_offset());
+                let producers_reader = ProducersSectionReader::new(binary_reader)?;
+
+                let mut field_index = 0;
+                for field in producers_reader.into_iter_with_offsets() {
+                    let (offset, field) = field?;
+
+                    if field_index != 0 {
+                        items.last_mut().unwrap().range.end = offset as u32;
+                    }
+
+                    let mut values = Vec::new();
+                    for value in field.values {
+                        let value = value?;
+                        values.push(format!("{} {}", value.name, value.version));
+                    }
+
+                    items.push(Item {
+                        range: Range {
+                            start: offset as u32,
+                            end: offset as u32,
+                        },
+                        raw_name: format!("producers {} ({})", field.name, values.join(", ")),
+                        display_name: String::new(),
+                    });
+
+                    field_index += 1;
+                }
+
+                if field_index != 0 {
+                    items.last_mut().unwrap().range.end = range.end as u32;
+                }
+            }
+
             Payload::End(_) => {
                 break;
             }
@@ -717,4 +974,155 @@ fn gather_items(mut bytes: &[u8]) -> anyhow::Result<Vec<Item>> {
     Ok(items)
 }
 
+/// Computes the alias set for every item in `items`, in the same order.
+///
+/// Unlike `display_name` — which the name section resolves to a single
+/// string per item — an item can have several searchable aliases: every
+/// name-section entry that targets it, plus whatever synonyms `overrides`
+/// supplies (keyed by `raw_name`, e.g. `"func 3"` -> `["memcpy_impl"]`).
+/// This mirrors rustdoc's `#[doc(alias = "...")]`: the extra names are
+/// indexed for search but never replace the primary `display_name`.
+///
+/// Ideally `aliases` would live directly on the wit `Item` record; until
+/// that lands, callers combine this with `items()` themselves.
+fn gather_item_aliases(
+    bytes: &[u8],
+    items: &[Item],
+    overrides: &HashMap<String, Vec<String>>,
+) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut by_item_name: HashMap<String, Vec<String>> = HashMap::new();
+    for alias in gather_aliases(bytes)? {
+        by_item_name.entry(alias.item_name).or_default().push(alias.name);
+    }
+    for (item_name, extra) in overrides {
+        by_item_name
+            .entry(item_name.clone())
+            .or_default()
+            .extend(extra.iter().cloned());
+    }
+
+    Ok(items
+        .iter()
+        .map(|item| by_item_name.get(&item.raw_name).cloned().unwrap_or_default())
+        .collect())
+}
+
+/// Rewrites the module/field names of import descriptors and re-encodes a
+/// valid module. `renames` maps `(old_module, old_field)` to `(new_module,
+/// new_field)`; imports with no entry in the map are left untouched.
+///
+/// Every section is re-emitted: untouched sections are copied byte-for-byte
+/// (id, size, and content together, tracked via the parser's `consumed`
+/// count rather than `SectionLimited::range()`, which only covers a
+/// section's content), and the import section alone is rebuilt from scratch:
+/// its id byte, then the LEB128 size and content that `wasm_encoder`
+/// encodes. Splicing in
+/// just the re-encoded *content* over the old section's content would leave
+/// the original id/size bytes in place ahead of a second, newly-computed
+/// size, corrupting the module.
+pub fn rewrite_imports(
+    bytes: &[u8],
+    renames: &HashMap<(String, String), (String, String)>,
+) -> anyhow::Result<Vec<u8>> {
+    use wasmparser::*;
+
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut found_imports = false;
+
+    let mut parser = Parser::new(0);
+    let mut rest = bytes;
+    loop {
+        let section_start = bytes.len() - rest.len();
+        let (payload, consumed) = match parser.parse(rest, true)? {
+            Chunk::NeedMoreData(_) => unreachable!(),
+            Chunk::Parsed { payload, consumed } => (payload, consumed),
+        };
+        rest = &rest[consumed..];
+
+        if let Payload::ImportSection(s) = payload {
+            found_imports = true;
+            let mut encoded = wasm_encoder::ImportSection::new();
+            for import in s {
+                let import = import?;
+                let (module, field) =
+                    match renames.get(&(import.module.to_owned(), import.name.to_owned())) {
+                        Some((module, field)) => (module.clone(), field.clone()),
+                        None => (import.module.to_owned(), import.name.to_owned()),
+                    };
+                encoded.import(&module, &field, convert_import_type(import.ty));
+            }
+            // `Encode` writes only the LEB128 size and content; the section
+            // id byte comes first.
+            out.push(wasm_encoder::SectionId::Import as u8);
+            wasm_encoder::Encode::encode(&encoded, &mut out);
+            continue;
+        }
+
+        out.extend_from_slice(&bytes[section_start..section_start + consumed]);
+        if matches!(payload, Payload::End(_)) {
+            break;
+        }
+    }
+
+    if !found_imports {
+        bail!("module has no import section to rewrite");
+    }
+
+    Ok(out)
+}
+
+fn convert_import_type(ty: wasmparser::TypeRef) -> wasm_encoder::EntityType {
+    match ty {
+        wasmparser::TypeRef::Func(idx) => wasm_encoder::EntityType::Function(idx),
+        wasmparser::TypeRef::Table(t) => wasm_encoder::EntityType::Table(wasm_encoder::TableType {
+            element_type: convert_ref_type(t.element_type),
+            minimum: t.initial,
+            maximum: t.maximum,
+        }),
+        wasmparser::TypeRef::Memory(m) => {
+            wasm_encoder::EntityType::Memory(wasm_encoder::MemoryType {
+                minimum: m.initial,
+                maximum: m.maximum,
+                memory64: m.memory64,
+                shared: m.shared,
+            })
+        }
+        wasmparser::TypeRef::Global(g) => {
+            wasm_encoder::EntityType::Global(wasm_encoder::GlobalType {
+                val_type: convert_val_type(g.content_type),
+                mutable: g.mutable,
+            })
+        }
+        wasmparser::TypeRef::Tag(t) => wasm_encoder::EntityType::Tag(wasm_encoder::TagType {
+            kind: wasm_encoder::TagKind::Exception,
+            func_type_idx: t.func_type_idx,
+        }),
+    }
+}
+
+fn convert_val_type(ty: wasmparser::ValType) -> wasm_encoder::ValType {
+    match ty {
+        wasmparser::ValType::I32 => wasm_encoder::ValType::I32,
+        wasmparser::ValType::I64 => wasm_encoder::ValType::I64,
+        wasmparser::ValType::F32 => wasm_encoder::ValType::F32,
+        wasmparser::ValType::F64 => wasm_encoder::ValType::F64,
+        wasmparser::ValType::V128 => wasm_encoder::ValType::V128,
+        wasmparser::ValType::Ref(r) => wasm_encoder::ValType::Ref(convert_ref_type(r)),
+    }
+}
+
+fn convert_ref_type(ty: wasmparser::RefType) -> wasm_encoder::RefType {
+    wasm_encoder::RefType {
+        nullable: ty.is_nullable(),
+        heap_type: match ty.heap_type() {
+            wasmparser::HeapType::Func => wasm_encoder::HeapType::Func,
+            wasmparser::HeapType::Extern => wasm_encoder::HeapType::Extern,
+            // The remaining GC/typed-function-reference heap types aren't
+            // exercised by this explorer yet; fall back to `func` rather
+            // than failing the whole rewrite.
+            _ => wasm_encoder::HeapType::Func,
+        },
+    }
+}
+
 bindings::export!(Component with_types_in bindings);
@@ -0,0 +1,117 @@
+//! A second pass over a decoded module that links items which reference
+//! each other — call sites to their callee, element/data segments to the
+//! table/memory/functions they populate — so a UI can offer goto-definition
+//! and find-all-uses.
+//!
+//! Function (and global/table/memory) indices are shared across the import
+//! and definition sections, exactly like the `raw_name` numbering
+//! `gather_items` already produces (e.g. `func 3` may be an import or a
+//! locally defined function). This pass resolves edges against that same
+//! numbering, so the import/definition split never has to be handled twice.
+
+use crate::bindings::exports::local::module::module::Item;
+use anyhow::Result;
+use std::collections::HashMap;
+use wasmparser::{Chunk, DataKind, ElementItem, ElementKind, Operator, Parser, Payload};
+
+pub struct ReferenceGraph {
+    forward: Vec<Vec<usize>>,
+    backward: Vec<Vec<usize>>,
+}
+
+impl ReferenceGraph {
+    pub fn build(bytes: &[u8], items: &[Item]) -> Result<ReferenceGraph> {
+        let mut by_name: HashMap<&str, usize> = HashMap::new();
+        let mut by_offset: HashMap<u32, usize> = HashMap::new();
+        for (i, item) in items.iter().enumerate() {
+            by_name.entry(item.raw_name.as_str()).or_insert(i);
+            by_offset.entry(item.range.start).or_insert(i);
+        }
+
+        let mut forward = vec![Vec::new(); items.len()];
+        let mut backward = vec![Vec::new(); items.len()];
+        let mut add_edge = |from: Option<usize>, to: Option<usize>| {
+            if let (Some(from), Some(to)) = (from, to) {
+                forward[from].push(to);
+                backward[to].push(from);
+            }
+        };
+
+        let mut parser = Parser::new(0);
+        let mut rest = bytes;
+        loop {
+            let payload = match parser.parse(rest, true)? {
+                Chunk::NeedMoreData(_) => unreachable!(),
+                Chunk::Parsed { payload, consumed } => {
+                    rest = &rest[consumed..];
+                    payload
+                }
+            };
+
+            match payload {
+                Payload::CodeSectionEntry(body) => {
+                    let mut ops = body.get_operators_reader()?;
+                    while !ops.eof() {
+                        let offset = ops.original_position() as u32;
+                        let op = ops.read()?;
+                        let from = by_offset.get(&offset).copied();
+
+                        match op {
+                            Operator::Call { function_index } | Operator::RefFunc { function_index } => {
+                                add_edge(from, by_name.get(format!("func {function_index}").as_str()).copied());
+                            }
+                            Operator::CallIndirect { table_index, .. } => {
+                                add_edge(from, by_name.get(format!("table {table_index}").as_str()).copied());
+                            }
+                            Operator::GlobalGet { global_index } | Operator::GlobalSet { global_index } => {
+                                add_edge(from, by_name.get(format!("global {global_index}").as_str()).copied());
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Payload::ElementSection(s) => {
+                    for (elem_index, element) in s.into_iter().enumerate() {
+                        let element = element?;
+                        let from = by_name.get(format!("elem {elem_index}").as_str()).copied();
+
+                        if let ElementKind::Active { table_index, .. } = element.kind {
+                            add_edge(from, by_name.get(format!("table {table_index}").as_str()).copied());
+                        }
+
+                        let mut item_reader = element.items.get_items_reader()?;
+                        for _ in 0..item_reader.get_count() {
+                            if let ElementItem::Func(function_index) = item_reader.read()? {
+                                add_edge(from, by_name.get(format!("func {function_index}").as_str()).copied());
+                            }
+                        }
+                    }
+                }
+                Payload::DataSection(s) => {
+                    for (data_index, data) in s.into_iter().enumerate() {
+                        let data = data?;
+                        let from = by_name.get(format!("data {data_index}").as_str()).copied();
+
+                        if let DataKind::Active { memory_index, .. } = data.kind {
+                            add_edge(from, by_name.get(format!("memory {memory_index}").as_str()).copied());
+                        }
+                    }
+                }
+                Payload::End(_) => break,
+                _ => {}
+            }
+        }
+
+        Ok(ReferenceGraph { forward, backward })
+    }
+
+    /// Items referenced *from* `item_index` (e.g. a call site's callee).
+    pub fn references_from(&self, item_index: usize) -> &[usize] {
+        &self.forward[item_index]
+    }
+
+    /// Items that reference `item_index` (e.g. all callers of a function).
+    pub fn references_to(&self, item_index: usize) -> &[usize] {
+        &self.backward[item_index]
+    }
+}
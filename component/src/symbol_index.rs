@@ -0,0 +1,143 @@
+//! Fuzzy name lookup over the items a module was decoded into.
+//!
+//! The index is a `fst::Map` from `display_name` to a slot in `entries`,
+//! which keeps it cheap to rebuild whenever only a small part of a module
+//! (e.g. just the name section) changes.
+
+use crate::bindings::exports::local::module::module::{Item, Range};
+use anyhow::Result;
+use fst::automaton::Levenshtein;
+use fst::{IntoStreamer, Map, MapBuilder, Streamer};
+
+pub struct SymbolIndex {
+    map: Map<Vec<u8>>,
+    entries: Vec<Vec<(usize, Range)>>,
+}
+
+pub struct SymbolMatch {
+    pub display_name: String,
+    pub item_index: usize,
+    pub range: Range,
+}
+
+impl SymbolIndex {
+    /// Builds an index over the `display_name` of every named item.
+    ///
+    /// `fst::Map` requires keys to be inserted in strictly increasing
+    /// lexicographic order, so names are sorted first; `fst::Map` also
+    /// requires each key to be distinct, so every item sharing a name is
+    /// bucketed behind that one key instead of being inserted again.
+    pub fn build(items: &[Item]) -> Result<SymbolIndex> {
+        let named: Vec<(&str, usize)> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !item.display_name.is_empty())
+            .map(|(i, item)| (item.display_name.as_str(), i))
+            .collect();
+        Self::build_from_names(items, named)
+    }
+
+    /// Same as [`SymbolIndex::build`], but every string in `aliases[i]` is
+    /// also indexed as a search key for `items[i]` (see
+    /// `gather_item_aliases`). Aliases never replace `display_name` — they
+    /// are additional keys pointing at the same item.
+    pub fn build_with_aliases(items: &[Item], aliases: &[Vec<String>]) -> Result<SymbolIndex> {
+        let mut named: Vec<(&str, usize)> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !item.display_name.is_empty())
+            .map(|(i, item)| (item.display_name.as_str(), i))
+            .collect();
+        for (item_index, item_aliases) in aliases.iter().enumerate() {
+            named.extend(item_aliases.iter().map(|alias| (alias.as_str(), item_index)));
+        }
+        Self::build_from_names(items, named)
+    }
+
+    fn build_from_names(items: &[Item], mut named: Vec<(&str, usize)>) -> Result<SymbolIndex> {
+        named.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut builder = MapBuilder::memory();
+        let mut entries: Vec<Vec<(usize, Range)>> = Vec::new();
+        let mut last_name: Option<&str> = None;
+        for (name, item_index) in named {
+            let range = Range {
+                start: items[item_index].range.start,
+                end: items[item_index].range.end,
+            };
+            if last_name == Some(name) {
+                entries
+                    .last_mut()
+                    .expect("last_name is only set once a bucket exists")
+                    .push((item_index, range));
+                continue;
+            }
+            builder.insert(name, entries.len() as u64)?;
+            entries.push(vec![(item_index, range)]);
+            last_name = Some(name);
+        }
+
+        Ok(SymbolIndex {
+            map: builder.into_map(),
+            entries,
+        })
+    }
+
+    /// Looks up `query` against the index, allowing up to 1 edit for short
+    /// queries (<= 5 chars) and up to 2 for longer ones. Results are ranked
+    /// exact-prefix matches first, then by edit distance, then by name
+    /// length.
+    pub fn fuzzy_search(&self, query: &str) -> Result<Vec<SymbolMatch>> {
+        let max_distance = if query.chars().count() <= 5 { 1 } else { 2 };
+        let automaton = Levenshtein::new(query, max_distance)?;
+
+        let mut matches = Vec::new();
+        let mut stream = self.map.search(&automaton).into_stream();
+        while let Some((name, value)) = stream.next() {
+            let name = std::str::from_utf8(name)?.to_owned();
+            let distance = levenshtein_distance(query, &name);
+            for &(item_index, range) in &self.entries[value as usize] {
+                matches.push((distance, name.clone(), item_index, range));
+            }
+        }
+
+        matches.sort_by(|a, b| {
+            let a_prefix = a.1.starts_with(query);
+            let b_prefix = b.1.starts_with(query);
+            b_prefix
+                .cmp(&a_prefix)
+                .then(a.0.cmp(&b.0))
+                .then(a.1.len().cmp(&b.1.len()))
+        });
+
+        Ok(matches
+            .into_iter()
+            .map(|(_, display_name, item_index, range)| SymbolMatch {
+                display_name,
+                item_index,
+                range,
+            })
+            .collect())
+    }
+}
+
+fn levenshtein_distance(a: &str, b: &str) -> u32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<u32> = (0..=b.len() as u32).collect();
+
+    for (i, &ac) in a.iter().enumerate() {
+        let mut prev_diag = row[0];
+        row[0] = i as u32 + 1;
+        for (j, &bc) in b.iter().enumerate() {
+            let cost = if ac == bc { 0 } else { 1 };
+            let new_value = (row[j + 1] + 1)
+                .min(row[j] + 1)
+                .min(prev_diag + cost);
+            prev_diag = row[j + 1];
+            row[j + 1] = new_value;
+        }
+    }
+
+    row[b.len()]
+}
@@ -5,7 +5,8 @@ use wat;
 use wasm_bindgen::prelude::*;
 use web_sys::Element;
 use wasmparser::*;
-use anyhow::{bail, Result};
+use anyhow::Result;
+use std::collections::HashMap;
 use std::fmt::Write;
 
 cfg_if! {
@@ -31,25 +32,26 @@ cfg_if! {
 }
 
 #[wasm_bindgen]
-pub fn input_text(text: &str, binary: &Element, explain: &Element) {
-    let (out_binary, out_explain) = run_input_text(text);
+pub fn input_text(text: &str, binary: &Element, explain: &Element, disasm: &Element) {
+    let (out_binary, out_explain, out_disasm) = run_input_text(text);
     binary.set_text_content(Some(&out_binary));
     explain.set_text_content(Some(&out_explain));
+    disasm.set_text_content(Some(&out_disasm));
 }
 
-fn run_input_text(text: &str) -> (String, String) {
+fn run_input_text(text: &str) -> (String, String, String) {
     let bytes = match wat::parse_str(&text) {
         Ok(binary) => binary,
         Err(err) => {
-            return (String::new(), format!("{}", err));
+            return (String::new(), format!("{}", err), String::new());
         },
     };
 
     let mut d = Dump::new(&bytes);
     if let Err(err) = d.run() {
-        return (String::new(), format!("{}", err));
+        return (String::new(), format!("{}", err), String::new());
     }
-    (d.binary, d.explain)
+    (d.binary, d.explain, d.disasm)
 }
 
 struct Dump<'a> {
@@ -58,6 +60,14 @@ struct Dump<'a> {
     state: String,
     binary: String,
     explain: String,
+    func_names: HashMap<u32, String>,
+    types: Vec<FuncType>,
+    func_type_indices: Vec<u32>,
+    globals: Vec<ValType>,
+    errors: HashMap<usize, String>,
+    disasm: String,
+    disasm_state: String,
+    indent: usize,
 }
 
 const NBYTES: usize = 4;
@@ -70,116 +80,217 @@ impl<'a> Dump<'a> {
             state: String::new(),
             binary: String::new(),
             explain: String::new(),
+            func_names: HashMap::new(),
+            types: Vec::new(),
+            func_type_indices: Vec::new(),
+            globals: Vec::new(),
+            errors: HashMap::new(),
+            disasm: String::new(),
+            disasm_state: String::new(),
+            indent: 0,
         }
     }
 
+    /// Dispatches on the binary's layer byte (offset 6-7 of the header,
+    /// right after the magic and version): 0 is a core module, 1 is a
+    /// component. See https://github.com/WebAssembly/component-model for
+    /// the header layout.
     fn run(&mut self) -> Result<()> {
-        let mut parser = ModuleReader::new(self.bytes)?;
-        write!(self.state, "version {}", parser.get_version())?;
-        self.print(parser.current_position())?;
-
-        let mut funcs = 0;
-        let mut globals = 0;
-        let mut tables = 0;
-        let mut memories = 0;
-
-        while !parser.eof() {
-            let section = parser.read()?;
-            write!(self.state, "section {:?}", section.code)?;
-            self.print(section.range().start)?;
-            match section.code {
-                SectionCode::Type => {
-                    self.print_iter(section.get_type_section_reader()?, |me, end, i| {
-                        write!(me.state, "type {:?}", i)?;
+        if self.bytes.len() >= 8 && self.bytes[6] == 1 && self.bytes[7] == 0 {
+            self.run_component()
+        } else {
+            self.run_module()
+        }
+    }
+
+    fn run_module(&mut self) -> Result<()> {
+        let mut funcs = 0u32;
+        let mut globals = 0u32;
+        let mut tables = 0u32;
+        let mut memories = 0u32;
+        let mut tags = 0u32;
+
+        let mut parser = Parser::new(0);
+        let mut rest = self.bytes;
+        loop {
+            let section_start = self.bytes.len() - rest.len();
+            let (payload, consumed) = match parser.parse(rest, true)? {
+                Chunk::NeedMoreData(_) => unreachable!(),
+                Chunk::Parsed { payload, consumed } => (payload, consumed),
+            };
+            rest = &rest[consumed..];
+            let section_end = section_start + consumed;
+
+            match payload {
+                Payload::Version { num, .. } => {
+                    write!(self.state, "version {}", num)?;
+                    self.print(section_end)?;
+                }
+                Payload::TypeSection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Type")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for rec_group in s {
+                        entries.extend(rec_group?.into_types_and_offsets());
+                    }
+                    self.print_items(range, entries, |me, end, ty| {
+                        write!(me.state, "type {:?}", ty)?;
+                        if let wasmparser::CompositeInnerType::Func(ft) = &ty.composite_type.inner {
+                            me.types.push(ft.clone());
+                        }
                         me.print(end)
                     })?
                 }
-                SectionCode::Import => {
-                    self.print_iter(section.get_import_section_reader()?, |me, end, i| {
+                Payload::ImportSection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Import")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        entries.push(item?);
+                    }
+                    self.print_items(range, entries, |me, end, import| {
                         write!(me.state, "import ")?;
-                        match i.ty {
-                            ImportSectionEntryType::Function(_) => {
+                        match import.ty {
+                            TypeRef::Func(type_index) => {
                                 write!(me.state, "[func {}]", funcs)?;
+                                me.func_type_indices.push(type_index);
                                 funcs += 1;
                             }
-                            ImportSectionEntryType::Memory(_) => {
+                            TypeRef::Memory(_) => {
                                 write!(me.state, "[memory {}]", memories)?;
                                 memories += 1;
                             }
-                            ImportSectionEntryType::Table(_) => {
+                            TypeRef::Table(_) => {
                                 write!(me.state, "[table {}]", tables)?;
                                 tables += 1;
                             }
-                            ImportSectionEntryType::Global(_) => {
+                            TypeRef::Global(ty) => {
                                 write!(me.state, "[global {}]", globals)?;
+                                me.globals.push(ty.content_type);
                                 globals += 1;
                             }
+                            TypeRef::Tag(_) => {
+                                write!(me.state, "[tag {}]", tags)?;
+                                tags += 1;
+                            }
                         }
-                        write!(me.state, " {:?}", i)?;
+                        write!(me.state, " {:?}", import)?;
                         me.print(end)
                     })?
                 }
-                SectionCode::Function => {
-                    let mut cnt = 0;
-                    self.print_iter(section.get_function_section_reader()?, |me, end, i| {
-                        write!(me.state, "[func {}] type {:?}", cnt + funcs, i)?;
+                Payload::FunctionSection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Function")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        entries.push(item?);
+                    }
+                    let mut cnt = 0u32;
+                    self.print_items(range, entries, |me, end, type_index| {
+                        write!(me.state, "[func {}] type {}", cnt + funcs, type_index)?;
+                        me.func_type_indices.push(*type_index);
                         cnt += 1;
                         me.print(end)
                     })?
                 }
-                SectionCode::Table => {
-                    self.print_iter(section.get_table_section_reader()?, |me, end, i| {
-                        write!(me.state, "[table {}] {:?}", tables, i)?;
+                Payload::TableSection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Table")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        entries.push(item?);
+                    }
+                    self.print_items(range, entries, |me, end, table| {
+                        write!(me.state, "[table {}] {:?}", tables, table)?;
                         tables += 1;
                         me.print(end)
                     })?
                 }
-                SectionCode::Memory => {
-                    self.print_iter(section.get_memory_section_reader()?, |me, end, i| {
-                        write!(me.state, "[memory {}] {:?}", memories, i)?;
+                Payload::MemorySection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Memory")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        entries.push(item?);
+                    }
+                    self.print_items(range, entries, |me, end, memory| {
+                        write!(me.state, "[memory {}] {:?}", memories, memory)?;
                         memories += 1;
                         me.print(end)
                     })?
                 }
-                SectionCode::Export => {
-                    self.print_iter(section.get_export_section_reader()?, |me, end, i| {
-                        write!(me.state, "export {:?}", i)?;
+                Payload::ExportSection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Export")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        entries.push(item?);
+                    }
+                    self.print_items(range, entries, |me, end, export| {
+                        write!(me.state, "export {:?}", export)?;
                         me.print(end)
                     })?
                 }
-                SectionCode::Global => {
-                    self.print_iter(section.get_global_section_reader()?, |me, _end, i| {
-                        write!(me.state, "[global {}] {:?}", globals, i.ty)?;
+                Payload::GlobalSection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Global")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        entries.push(item?);
+                    }
+                    self.print_items(range, entries, |me, _end, global| {
+                        write!(me.state, "[global {}] {:?}", globals, global.ty)?;
+                        me.globals.push(global.ty.content_type);
                         globals += 1;
-                        me.print(i.init_expr.get_binary_reader().original_position())?;
-                        me.print_ops(i.init_expr.get_operators_reader())
+                        me.print(global.init_expr.get_binary_reader().original_position())?;
+                        me.print_ops(global.init_expr.get_operators_reader())
                     })?
                 }
-                SectionCode::Start => {
-                    let start = section.get_start_section_content()?;
-                    write!(self.state, "start function {}", start)?;
-                    self.print(section.range().end)?;
-                }
-                SectionCode::DataCount => {
-                    let start = section.get_data_count_section_content()?;
-                    write!(self.state, "data count {}", start)?;
-                    self.print(section.range().end)?;
-                }
-                SectionCode::Element => {
-                    self.print_iter(section.get_element_section_reader()?, |me, _end, i| {
-                        write!(me.state, "element {:?}", i.ty)?;
-                        let mut items = i.items.get_items_reader()?;
-                        match i.kind {
+                Payload::StartSection { func, range } => {
+                    write!(self.state, "start function {}", func)?;
+                    self.print(range.end)?;
+                }
+                Payload::DataCountSection { count, range } => {
+                    write!(self.state, "data count {}", count)?;
+                    self.print(range.end)?;
+                }
+                Payload::ElementSection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Element")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        entries.push(item?);
+                    }
+                    self.print_items(range, entries, |me, _end, element| {
+                        write!(me.state, "element {:?}", element.ty)?;
+                        let mut items = element.items.get_items_reader()?;
+                        match element.kind {
                             ElementKind::Passive => {
                                 write!(me.state, " passive, {} items", items.get_count())?;
                             }
                             ElementKind::Active {
                                 table_index,
-                                init_expr,
+                                offset_expr,
                             } => {
-                                write!(me.state, " table[{}]", table_index)?;
-                                me.print(init_expr.get_binary_reader().original_position())?;
-                                me.print_ops(init_expr.get_operators_reader())?;
+                                write!(me.state, " table[{}]", table_index.unwrap_or(0))?;
+                                me.print(offset_expr.get_binary_reader().original_position())?;
+                                me.print_ops(offset_expr.get_operators_reader())?;
                                 write!(me.state, "{} items", items.get_count())?;
                             }
                             ElementKind::Declared => {
@@ -196,56 +307,118 @@ impl<'a> Dump<'a> {
                     })?
                 }
 
-                SectionCode::Data => {
-                    self.print_iter(section.get_data_section_reader()?, |me, end, i| {
-                        match i.kind {
+                Payload::DataSection(s) => {
+                    let range = s.range();
+                    write!(self.state, "section Data")?;
+                    self.print(range.start)?;
+
+                    let mut entries = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        entries.push(item?);
+                    }
+                    self.print_items(range, entries, |me, end, data| {
+                        match data.kind {
                             DataKind::Passive => {
                                 write!(me.state, "data passive")?;
-                                me.print(end - i.data.len())?;
+                                me.print(end - data.data.len())?;
                             }
                             DataKind::Active {
                                 memory_index,
-                                init_expr,
+                                offset_expr,
                             } => {
                                 write!(me.state, "data memory[{}]", memory_index)?;
-                                me.print(init_expr.get_binary_reader().original_position())?;
-                                me.print_ops(init_expr.get_operators_reader())?;
+                                me.print(offset_expr.get_binary_reader().original_position())?;
+                                me.print_ops(offset_expr.get_operators_reader())?;
                             }
                         }
                         write!(me.binary, "0x{:04x} |", me.cur)?;
                         for _ in 0..NBYTES {
                             write!(me.binary, "---")?;
                         }
-                        write!(me.binary, "-| ... {} bytes of data\n", i.data.len())?;
+                        write!(me.binary, "-| ... {} bytes of data\n", data.data.len())?;
                         me.cur = end;
                         Ok(())
                     })?
                 }
 
-                SectionCode::Code => {
-                    self.print_iter(section.get_code_section_reader()?, |me, _end, i| {
-                        write!(
-                            me.binary,
+                Payload::CodeSectionStart { count, range, .. } => {
+                    write!(self.state, "section Code")?;
+                    self.print(range.start)?;
+                    write!(self.state, "{} count", count)?;
+                    self.print(section_end)?;
+                }
+
+                Payload::CodeSectionEntry(body) => {
+                    let current_func = funcs;
+                    match self.func_names.get(&current_func) {
+                        Some(name) => write!(
+                            self.binary,
+                            "============== func {} ({}) ====================\n",
+                            current_func, name
+                        )?,
+                        None => write!(
+                            self.binary,
                             "============== func {} ====================\n",
-                            funcs
-                        )?;
-                        funcs += 1;
-                        write!(me.state, "size of function")?;
-                        me.print(i.get_binary_reader().original_position())?;
-                        let mut locals = i.get_locals_reader()?;
-                        write!(me.state, "{} local blocks", locals.get_count())?;
-                        me.print(locals.original_position())?;
-                        for _ in 0..locals.get_count() {
-                            let (amt, ty) = locals.read()?;
-                            write!(me.state, "{} locals of type {:?}", amt, ty)?;
-                            me.print(locals.original_position())?;
-                        }
-                        me.print_ops(i.get_operators_reader()?)?;
-                        Ok(())
-                    })?
+                            current_func
+                        )?,
+                    }
+                    funcs += 1;
+                    write!(self.state, "size of function")?;
+
+                    let signature = self
+                        .func_type_indices
+                        .get(current_func as usize)
+                        .and_then(|type_index| self.types.get(*type_index as usize))
+                        .cloned();
+                    self.indent = 0;
+                    write!(
+                        self.disasm_state,
+                        "(func ${}{}",
+                        current_func,
+                        signature.as_ref().map(format_functype).unwrap_or_default()
+                    )?;
+                    self.print(body.range().start)?;
+                    self.indent = 1;
+
+                    let mut locals: Vec<ValType> = match &signature {
+                        Some(ft) => ft.params().to_vec(),
+                        None => Vec::new(),
+                    };
+
+                    let mut locals_reader = body.get_locals_reader()?;
+                    write!(self.state, "{} local blocks", locals_reader.get_count())?;
+                    self.print(locals_reader.original_position())?;
+                    for _ in 0..locals_reader.get_count() {
+                        let (amt, ty) = locals_reader.read()?;
+                        write!(self.state, "{} locals of type {:?}", amt, ty)?;
+                        locals.extend(std::iter::repeat(ty).take(amt as usize));
+                        self.print(locals_reader.original_position())?;
+                    }
+
+                    let ops = locals_reader.into_operators_reader()?;
+                    let types = self.types.clone();
+                    let func_type_indices = self.func_type_indices.clone();
+                    let globals = self.globals.clone();
+                    self.print_ops_validated(
+                        ops,
+                        &locals,
+                        &globals,
+                        &types,
+                        &func_type_indices,
+                        signature.as_ref(),
+                    )?;
+                }
+
+                Payload::CustomSection(reader) if reader.name() == "name" => {
+                    write!(self.state, "section Custom {{ name: \"name\" }}")?;
+                    self.print(reader.range().start)?;
+                    let binary_reader = BinaryReader::new(reader.data(), reader.data_offset());
+                    self.print_name_section(NameSectionReader::new(binary_reader))?;
                 }
 
-                SectionCode::Custom { .. } => {
+                Payload::CustomSection(reader) => {
+                    write!(self.state, "section Custom {{ name: {:?} }}", reader.name())?;
+                    self.print(reader.range().start)?;
                     write!(self.binary, "0x{:04x} |", self.cur)?;
                     for _ in 0..NBYTES {
                         write!(self.binary, "---")?;
@@ -253,9 +426,20 @@ impl<'a> Dump<'a> {
                     write!(
                         self.binary,
                         "-| ... {} bytes of data\n",
-                        section.get_binary_reader().bytes_remaining()
+                        section_end - self.cur
                     )?;
-                    self.cur = section.range().end;
+                    self.cur = section_end;
+                }
+
+                Payload::End(_) => break,
+
+                // Sections this explorer doesn't special-case (e.g. the
+                // exception-handling tag section): dump all of their bytes
+                // under a generic "section (unhandled)" label rather than
+                // silently dropping them.
+                _ => {
+                    write!(self.state, "section (unhandled)")?;
+                    self.print(section_end)?;
                 }
             }
         }
@@ -264,26 +448,131 @@ impl<'a> Dump<'a> {
         Ok(())
     }
 
-    fn print_iter<T>(
+    /// Prints a section's "N count" header followed by one call to `f` per
+    /// item, with `f` responsible for advancing `self.cur` up to the `end`
+    /// it's handed (usually via `self.print(end)`, but some items — globals,
+    /// elements, data segments — need several finer-grained prints of their
+    /// own, e.g. to break out a nested init expression's operators).
+    fn print_items<T>(
         &mut self,
-        mut iter: T,
-        mut print: impl FnMut(&mut Self, usize, T::Item) -> Result<()>,
-    ) -> Result<()>
-    where
-        T: SectionReader + SectionWithLimitedItems,
-    {
-        write!(self.state, "{} count", iter.get_count())?;
-        self.print(iter.original_position())?;
-        for _ in 0..iter.get_count() {
-            let item = iter.read()?;
-            print(self, iter.original_position(), item)?;
+        range: std::ops::Range<usize>,
+        entries: Vec<(usize, T)>,
+        mut f: impl FnMut(&mut Self, usize, &T) -> Result<()>,
+    ) -> Result<()> {
+        write!(self.state, "{} count", entries.len())?;
+        let first = entries.first().map(|(offset, _)| *offset).unwrap_or(range.end);
+        self.print(first)?;
+
+        let len = entries.len();
+        for idx in 0..len {
+            let end = if idx + 1 < len {
+                entries[idx + 1].0
+            } else {
+                range.end
+            };
+            f(self, end, &entries[idx].1)?;
         }
-        if !iter.eof() {
-            bail!("too many bytes in section");
+        Ok(())
+    }
+
+    /// Walks a component binary. Component-model sections (types, imports,
+    /// instances, aliases, canonical lowering/lifting, ...) don't have the
+    /// per-item `print_items` treatment the core-module arms above get; this
+    /// is a coarser, section-at-a-time dump of each one's byte range. A
+    /// nested `Payload::ModuleSection`/`ComponentSection` recurses into a
+    /// fresh `Dump` over just those bytes, with its `explain`/`disasm`
+    /// output indented so the nesting reads clearly alongside the outer
+    /// component's ranges.
+    ///
+    /// A single `Parser` drives the whole walk from byte offset 0 (the
+    /// `Payload::Version` event covers the magic/version/layer preamble
+    /// itself, the same way `run_module` handles it) and `rest` is advanced
+    /// by each `Chunk::Parsed`'s `consumed`, so the parser's internal state
+    /// never drifts out of sync with the slice it's fed.
+    fn run_component(&mut self) -> Result<()> {
+        let mut parser = Parser::new(0);
+        let mut rest = self.bytes;
+        loop {
+            let section_start = self.bytes.len() - rest.len();
+            let (payload, consumed) = match parser.parse(rest, true)? {
+                Chunk::NeedMoreData(_) => unreachable!(),
+                Chunk::Parsed { payload, consumed } => (payload, consumed),
+            };
+            rest = &rest[consumed..];
+            let section_end = section_start + consumed;
+
+            match payload {
+                Payload::Version { .. } => {
+                    write!(self.state, "component header (magic + version + layer)")?;
+                    self.print(section_end)?;
+                }
+                Payload::ModuleSection { range, .. } => {
+                    write!(self.state, "nested core module")?;
+                    self.print(range.start)?;
+                    let mut nested = Dump::new(&self.bytes[range.start..range.end]);
+                    nested.run_module()?;
+                    self.splice_nested(&nested, range.end);
+                    // `consumed` above only covered this section's header; the
+                    // nested module's own bytes were parsed by `nested`'s
+                    // independent `Parser`, not this one, so skip the parent
+                    // walk past them here to keep `rest`/`self.cur` in sync.
+                    rest = &self.bytes[range.end..];
+                }
+                Payload::ComponentSection { range, .. } => {
+                    write!(self.state, "nested component")?;
+                    self.print(range.start)?;
+                    let mut nested = Dump::new(&self.bytes[range.start..range.end]);
+                    nested.run_component()?;
+                    self.splice_nested(&nested, range.end);
+                    rest = &self.bytes[range.end..];
+                }
+                Payload::ComponentTypeSection(s) => self.print_component_section("component types", s.range())?,
+                Payload::CoreTypeSection(s) => self.print_component_section("core types", s.range())?,
+                Payload::ComponentImportSection(s) => {
+                    self.print_component_section("component imports", s.range())?
+                }
+                Payload::ComponentExportSection(s) => {
+                    self.print_component_section("component exports", s.range())?
+                }
+                Payload::ComponentInstanceSection(s) => {
+                    self.print_component_section("component instances", s.range())?
+                }
+                Payload::ComponentAliasSection(s) => {
+                    self.print_component_section("component aliases", s.range())?
+                }
+                Payload::ComponentCanonicalSection(s) => {
+                    self.print_component_section("canonical functions", s.range())?
+                }
+                Payload::ComponentStartSection { range, .. } => {
+                    self.print_component_section("component start", range)?
+                }
+                Payload::CustomSection(reader) => {
+                    let label = format!("custom \"{}\"", reader.name());
+                    self.print_component_section(&label, reader.range())?
+                }
+                Payload::End(_) => break,
+                _ => {
+                    self.print(section_end)?;
+                }
+            }
         }
+
         Ok(())
     }
 
+    fn print_component_section(&mut self, label: &str, range: std::ops::Range<usize>) -> Result<()> {
+        write!(self.state, "{}", label)?;
+        self.print(range.end)?;
+        Ok(())
+    }
+
+    fn splice_nested(&mut self, nested: &Dump, end: usize) {
+        self.binary.push_str(&nested.binary);
+        self.explain.push_str(&indent_text(&nested.explain));
+        self.disasm.push_str(&indent_text(&nested.disasm));
+        self.cur = end;
+    }
+
     fn print_ops(&mut self, mut i: OperatorsReader) -> Result<()> {
         while !i.eof() {
             match i.read() {
@@ -295,9 +584,146 @@ impl<'a> Dump<'a> {
         Ok(())
     }
 
+    /// Like `print_ops`, but also walks a virtual operand-type stack
+    /// alongside the declared `locals` (params followed by the function's
+    /// own locals), `globals` and `types`/`func_type_indices` (for
+    /// `call`/`call_indirect` arity) and `signature`, recording a diagnostic
+    /// in `self.errors` keyed to the offending instruction's start offset
+    /// whenever the stack underflows or an operand's type doesn't match.
+    /// `print` picks the error back up when it renders that instruction's
+    /// range, so the whole function keeps printing instead of aborting at
+    /// the first mismatch. Once an operator outside the modeled MVP subset
+    /// is seen, the stack is poisoned (see `apply_operator_to_stack`) so the
+    /// rest of the function isn't flagged based on a guess.
+    fn print_ops_validated(
+        &mut self,
+        mut i: OperatorsReader,
+        locals: &[ValType],
+        globals: &[ValType],
+        types: &[FuncType],
+        func_type_indices: &[u32],
+        signature: Option<&FuncType>,
+    ) -> Result<()> {
+        let mut stack: Option<Vec<ValType>> = Some(Vec::new());
+
+        while !i.eof() {
+            let start = i.original_position();
+            match i.read() {
+                Ok(op) => {
+                    write!(self.state, "{:?}", op)?;
+                    if let Err(msg) = apply_operator_to_stack(
+                        &op,
+                        locals,
+                        globals,
+                        types,
+                        func_type_indices,
+                        signature,
+                        &mut stack,
+                    ) {
+                        self.errors.insert(start, msg);
+                    }
+                    let mut indent = self.indent;
+                    let text = operator_disasm(&op, &mut indent);
+                    self.indent = indent;
+                    self.disasm_state.push_str(&text);
+                }
+                Err(_) => write!(self.state, "??")?,
+            }
+            self.print(i.original_position())?;
+        }
+        Ok(())
+    }
+
+    /// Decodes the "name" custom section, printing each subsection's
+    /// entries against their byte range the same way `print_items` does for
+    /// the other sections. Function names are also stashed in
+    /// `self.func_names` so a later function body's header can show the
+    /// symbolic name alongside the numeric index.
+    fn print_name_section(&mut self, reader: NameSectionReader) -> Result<()> {
+        for subsection in reader {
+            match subsection? {
+                Name::Module { name, name_range } => {
+                    write!(self.state, "module name {:?}", name)?;
+                    self.print(name_range.end)?;
+                }
+                Name::Function(map) => {
+                    let range = map.range();
+                    let mut entries = Vec::new();
+                    for naming in map.into_iter_with_offsets() {
+                        entries.push(naming?);
+                    }
+                    self.print_items(range, entries, |me, end, naming| {
+                        me.func_names.insert(naming.index, naming.name.to_string());
+                        write!(me.state, "[func {}] name {:?}", naming.index, naming.name)?;
+                        me.print(end)
+                    })?
+                }
+                Name::Type(map) => self.print_direct_names(map, "type")?,
+                Name::Tag(map) => self.print_direct_names(map, "tag")?,
+                Name::Table(map) => self.print_direct_names(map, "table")?,
+                Name::Memory(map) => self.print_direct_names(map, "memory")?,
+                Name::Global(map) => self.print_direct_names(map, "global")?,
+                Name::Element(map) => self.print_direct_names(map, "elem")?,
+                Name::Data(map) => self.print_direct_names(map, "data")?,
+                Name::Local(indirect) => self.print_indirect_names(indirect, "func")?,
+                Name::Label(indirect) => self.print_indirect_names(indirect, "label in func")?,
+                Name::Unknown { range, .. } => {
+                    write!(self.state, "unhandled name subsection")?;
+                    self.print(range.end)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Shared by every name subsection whose entries are a flat `Naming`
+    /// list (types, tags, tables, memories, globals, elements, data) —
+    /// `Name::Function` gets its own arm above since it also populates
+    /// `self.func_names`.
+    fn print_direct_names(&mut self, map: NameMap, label: &str) -> Result<()> {
+        let range = map.range();
+        let mut entries = Vec::new();
+        for naming in map.into_iter_with_offsets() {
+            entries.push(naming?);
+        }
+        self.print_items(range, entries, |me, end, naming| {
+            write!(me.state, "[{} {}] name {:?}", label, naming.index, naming.name)?;
+            me.print(end)
+        })
+    }
+
+    /// Shared by the two name subsections that map an index to a nested
+    /// list of names (`Name::Local` and `Name::Label`).
+    fn print_indirect_names(&mut self, indirect: IndirectNameMap, owner: &str) -> Result<()> {
+        let range = indirect.range();
+        let mut entries = Vec::new();
+        for group in indirect.into_iter_with_offsets() {
+            entries.push(group?);
+        }
+        self.print_items(range, entries, |me, _end, group| {
+            write!(
+                me.state,
+                "[{} {}] {} names",
+                owner,
+                group.index,
+                group.names.count()
+            )?;
+            let names_range = group.names.range();
+            let mut names = Vec::new();
+            for naming in group.names.clone().into_iter_with_offsets() {
+                names.push(naming?);
+            }
+            me.print_items(names_range, names, |me, end, naming| {
+                write!(me.state, "name {:?}", naming.name)?;
+                me.print(end)
+            })
+        })
+    }
+
     fn print(&mut self, end: usize) -> Result<()> {
         assert!(self.cur < end);
         let bytes = &self.bytes[self.cur..end];
+        let error = self.errors.remove(&self.cur);
         write!(self.binary, "0x{:04x} |", self.cur)?;
         for (i, chunk) in bytes.chunks(NBYTES).enumerate() {
             if i > 0 {
@@ -313,11 +739,627 @@ impl<'a> Dump<'a> {
             if i == 0 {
                 self.explain.push_str(&self.state);
                 self.state.truncate(0);
+                if let Some(msg) = &error {
+                    write!(self.explain, "  !! {}", msg)?;
+                }
+
+                for _ in 0..self.indent {
+                    self.disasm.push_str("  ");
+                }
+                self.disasm.push_str(&self.disasm_state);
+                self.disasm_state.truncate(0);
             }
             self.explain.push_str("\n");
             self.binary.push_str("\n");
+            self.disasm.push_str("\n");
         }
         self.cur = end;
         Ok(())
     }
 }
+
+/// Indents every line of a nested `Dump`'s output by one level, so a
+/// module nested inside a component reads visually distinct from the
+/// component's own sections.
+fn indent_text(text: &str) -> String {
+    text.lines().map(|l| format!("  {}\n", l)).collect()
+}
+
+fn format_valtype(ty: ValType) -> String {
+    format!("{:?}", ty).to_lowercase()
+}
+
+fn format_functype(ft: &FuncType) -> String {
+    let mut s = String::new();
+    for p in ft.params().iter() {
+        write!(s, " (param {})", format_valtype(*p)).ok();
+    }
+    for r in ft.results().iter() {
+        write!(s, " (result {})", format_valtype(*r)).ok();
+    }
+    s
+}
+
+/// Renders an operator as a WAT-ish textual form (`call $3`, `local.get
+/// $0`, ...), updating `indent` for block/loop/if nesting as a side effect
+/// so the caller can apply it to subsequent lines. Only a handful of
+/// control-flow and indexed operators get special-cased; everything else
+/// falls back to a dotted form of its Rust variant name.
+fn operator_disasm(op: &Operator, indent: &mut usize) -> String {
+    use Operator::*;
+
+    if matches!(op, End | Else) {
+        *indent = indent.saturating_sub(1);
+    }
+
+    let text = match op {
+        Block { .. } => "block".to_owned(),
+        Loop { .. } => "loop".to_owned(),
+        If { .. } => "if".to_owned(),
+        Else => "else".to_owned(),
+        End if *indent == 0 => "end)".to_owned(),
+        End => "end".to_owned(),
+        Call { function_index } => format!("call ${}", function_index),
+        CallIndirect { table_index, .. } => format!("call_indirect (table {})", table_index),
+        LocalGet { local_index } => format!("local.get ${}", local_index),
+        LocalSet { local_index } => format!("local.set ${}", local_index),
+        LocalTee { local_index } => format!("local.tee ${}", local_index),
+        GlobalGet { global_index } => format!("global.get ${}", global_index),
+        GlobalSet { global_index } => format!("global.set ${}", global_index),
+        I32Const { value } => format!("i32.const {}", value),
+        I64Const { value } => format!("i64.const {}", value),
+        other => {
+            let debug = format!("{:?}", other);
+            let name = debug
+                .split(|c: char| c == ' ' || c == '{')
+                .next()
+                .unwrap_or(&debug);
+            let mut mnemonic = String::new();
+            for (i, ch) in name.chars().enumerate() {
+                if ch.is_ascii_uppercase() && i != 0 {
+                    mnemonic.push('.');
+                }
+                mnemonic.extend(ch.to_lowercase());
+            }
+            mnemonic
+        }
+    };
+
+    if matches!(op, Block { .. } | Loop { .. } | If { .. } | Else) {
+        *indent += 1;
+    }
+
+    text
+}
+
+/// Applies `op`'s operand-type effect to the virtual stack, returning a
+/// diagnostic string (rather than bailing) on underflow or a type mismatch.
+/// This models the common MVP numeric/local/global/memory/control/call
+/// instructions. `stack` is `None` once an operator outside that modeled
+/// subset has been seen (see `is_modeled`) — from that point on this is a
+/// no-op, since without knowing the unmodeled op's effect the virtual stack
+/// can no longer be trusted and a guess would risk a false positive on
+/// every instruction that follows it.
+fn apply_operator_to_stack(
+    op: &Operator,
+    locals: &[ValType],
+    globals: &[ValType],
+    types: &[FuncType],
+    func_type_indices: &[u32],
+    signature: Option<&FuncType>,
+    stack: &mut Option<Vec<ValType>>,
+) -> std::result::Result<(), String> {
+    if stack.is_none() {
+        return Ok(());
+    }
+    if !is_modeled(op) {
+        *stack = None;
+        return Ok(());
+    }
+
+    fn pop(stack: &mut Vec<ValType>, expected: ValType) -> std::result::Result<(), String> {
+        match stack.pop() {
+            Some(ty) if ty == expected => Ok(()),
+            Some(ty) => Err(format!("type mismatch: expected {:?}, got {:?}", expected, ty)),
+            None => Err(format!("stack underflow: expected {:?}", expected)),
+        }
+    }
+
+    fn local_type(locals: &[ValType], local_index: u32) -> std::result::Result<ValType, String> {
+        locals
+            .get(local_index as usize)
+            .copied()
+            .ok_or_else(|| format!("local index {} out of bounds", local_index))
+    }
+
+    fn global_type(globals: &[ValType], global_index: u32) -> std::result::Result<ValType, String> {
+        globals
+            .get(global_index as usize)
+            .copied()
+            .ok_or_else(|| format!("global index {} out of bounds", global_index))
+    }
+
+    fn call_type<'a>(
+        types: &'a [FuncType],
+        func_type_indices: &[u32],
+        function_index: u32,
+    ) -> std::result::Result<&'a FuncType, String> {
+        let type_index = func_type_indices
+            .get(function_index as usize)
+            .copied()
+            .ok_or_else(|| format!("function index {} out of bounds", function_index))?;
+        types
+            .get(type_index as usize)
+            .ok_or_else(|| format!("type index {} out of bounds", type_index))
+    }
+
+    fn pop_cmp(stack: &mut Vec<ValType>, operand: ValType) -> std::result::Result<(), String> {
+        pop(stack, operand)?;
+        pop(stack, operand)?;
+        stack.push(ValType::I32);
+        Ok(())
+    }
+
+    fn pop_load_addr(stack: &mut Vec<ValType>, result: ValType) -> std::result::Result<(), String> {
+        pop(stack, ValType::I32)?;
+        stack.push(result);
+        Ok(())
+    }
+
+    fn pop_store(stack: &mut Vec<ValType>, value: ValType) -> std::result::Result<(), String> {
+        pop(stack, value)?;
+        pop(stack, ValType::I32)?;
+        Ok(())
+    }
+
+    let stack = stack.as_mut().unwrap();
+
+    match op {
+        Operator::I32Const { .. } => stack.push(ValType::I32),
+        Operator::I64Const { .. } => stack.push(ValType::I64),
+        Operator::F32Const { .. } => stack.push(ValType::F32),
+        Operator::F64Const { .. } => stack.push(ValType::F64),
+
+        Operator::LocalGet { local_index } => stack.push(local_type(locals, *local_index)?),
+        Operator::LocalSet { local_index } => pop(stack, local_type(locals, *local_index)?)?,
+        Operator::LocalTee { local_index } => {
+            let ty = local_type(locals, *local_index)?;
+            pop(stack, ty)?;
+            stack.push(ty);
+        }
+
+        Operator::GlobalGet { global_index } => stack.push(global_type(globals, *global_index)?),
+        Operator::GlobalSet { global_index } => pop(stack, global_type(globals, *global_index)?)?,
+
+        Operator::I32Add | Operator::I32Sub | Operator::I32Mul => {
+            pop(stack, ValType::I32)?;
+            pop(stack, ValType::I32)?;
+            stack.push(ValType::I32);
+        }
+        Operator::I64Add | Operator::I64Sub | Operator::I64Mul => {
+            pop(stack, ValType::I64)?;
+            pop(stack, ValType::I64)?;
+            stack.push(ValType::I64);
+        }
+
+        Operator::I32Eqz => {
+            pop(stack, ValType::I32)?;
+            stack.push(ValType::I32);
+        }
+        Operator::I64Eqz => {
+            pop(stack, ValType::I64)?;
+            stack.push(ValType::I32);
+        }
+        Operator::I32Eq
+        | Operator::I32Ne
+        | Operator::I32LtS
+        | Operator::I32LtU
+        | Operator::I32GtS
+        | Operator::I32GtU
+        | Operator::I32LeS
+        | Operator::I32LeU
+        | Operator::I32GeS
+        | Operator::I32GeU => pop_cmp(stack, ValType::I32)?,
+        Operator::I64Eq
+        | Operator::I64Ne
+        | Operator::I64LtS
+        | Operator::I64LtU
+        | Operator::I64GtS
+        | Operator::I64GtU
+        | Operator::I64LeS
+        | Operator::I64LeU
+        | Operator::I64GeS
+        | Operator::I64GeU => pop_cmp(stack, ValType::I64)?,
+        Operator::F32Eq
+        | Operator::F32Ne
+        | Operator::F32Lt
+        | Operator::F32Gt
+        | Operator::F32Le
+        | Operator::F32Ge => pop_cmp(stack, ValType::F32)?,
+        Operator::F64Eq
+        | Operator::F64Ne
+        | Operator::F64Lt
+        | Operator::F64Gt
+        | Operator::F64Le
+        | Operator::F64Ge => pop_cmp(stack, ValType::F64)?,
+
+        Operator::I32Load { .. }
+        | Operator::I32Load8S { .. }
+        | Operator::I32Load8U { .. }
+        | Operator::I32Load16S { .. }
+        | Operator::I32Load16U { .. } => pop_load_addr(stack, ValType::I32)?,
+        Operator::I64Load { .. }
+        | Operator::I64Load8S { .. }
+        | Operator::I64Load8U { .. }
+        | Operator::I64Load16S { .. }
+        | Operator::I64Load16U { .. }
+        | Operator::I64Load32S { .. }
+        | Operator::I64Load32U { .. } => pop_load_addr(stack, ValType::I64)?,
+        Operator::F32Load { .. } => pop_load_addr(stack, ValType::F32)?,
+        Operator::F64Load { .. } => pop_load_addr(stack, ValType::F64)?,
+
+        Operator::I32Store { .. } | Operator::I32Store8 { .. } | Operator::I32Store16 { .. } => {
+            pop_store(stack, ValType::I32)?
+        }
+        Operator::I64Store { .. }
+        | Operator::I64Store8 { .. }
+        | Operator::I64Store16 { .. }
+        | Operator::I64Store32 { .. } => pop_store(stack, ValType::I64)?,
+        Operator::F32Store { .. } => pop_store(stack, ValType::F32)?,
+        Operator::F64Store { .. } => pop_store(stack, ValType::F64)?,
+
+        Operator::MemorySize { .. } => stack.push(ValType::I32),
+        Operator::MemoryGrow { .. } => {
+            pop(stack, ValType::I32)?;
+            stack.push(ValType::I32);
+        }
+
+        Operator::Select => {
+            pop(stack, ValType::I32)?;
+            let val2 = stack.pop().ok_or_else(|| "stack underflow: select".to_owned())?;
+            let val1 = stack.pop().ok_or_else(|| "stack underflow: select".to_owned())?;
+            if val1 != val2 {
+                return Err(format!("type mismatch: select arms {:?} vs {:?}", val1, val2));
+            }
+            stack.push(val1);
+        }
+
+        Operator::BrIf { .. } => pop(stack, ValType::I32)?,
+
+        Operator::Call { function_index } => {
+            let ft = call_type(types, func_type_indices, *function_index)?.clone();
+            for ty in ft.params().iter().rev() {
+                pop(stack, *ty)?;
+            }
+            for ty in ft.results().iter() {
+                stack.push(*ty);
+            }
+        }
+        Operator::CallIndirect { type_index, .. } => {
+            let ft = types
+                .get(*type_index as usize)
+                .ok_or_else(|| format!("type index {} out of bounds", type_index))?
+                .clone();
+            pop(stack, ValType::I32)?;
+            for ty in ft.params().iter().rev() {
+                pop(stack, *ty)?;
+            }
+            for ty in ft.results().iter() {
+                stack.push(*ty);
+            }
+        }
+
+        Operator::Drop => {
+            stack.pop().ok_or_else(|| "stack underflow: drop".to_owned())?;
+        }
+        Operator::Return => {
+            if let Some(sig) = signature {
+                for ty in sig.results().iter().rev() {
+                    pop(stack, *ty)?;
+                }
+            }
+        }
+        Operator::If { .. } => pop(stack, ValType::I32)?,
+        Operator::End | Operator::Block { .. } | Operator::Loop { .. } => {}
+
+        _ => unreachable!("is_modeled should have filtered this op out"),
+    }
+
+    Ok(())
+}
+
+/// The subset of operators `apply_operator_to_stack` knows the stack effect
+/// of. Kept as its own predicate (rather than a `_ => poison` arm alongside
+/// the real handling) so poisoning the stack never has to run while it's
+/// already mutably borrowed for the modeled case.
+fn is_modeled(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::I32Const { .. }
+            | Operator::I64Const { .. }
+            | Operator::F32Const { .. }
+            | Operator::F64Const { .. }
+            | Operator::LocalGet { .. }
+            | Operator::LocalSet { .. }
+            | Operator::LocalTee { .. }
+            | Operator::GlobalGet { .. }
+            | Operator::GlobalSet { .. }
+            | Operator::I32Add
+            | Operator::I32Sub
+            | Operator::I32Mul
+            | Operator::I64Add
+            | Operator::I64Sub
+            | Operator::I64Mul
+            | Operator::I32Eqz
+            | Operator::I64Eqz
+            | Operator::I32Eq
+            | Operator::I32Ne
+            | Operator::I32LtS
+            | Operator::I32LtU
+            | Operator::I32GtS
+            | Operator::I32GtU
+            | Operator::I32LeS
+            | Operator::I32LeU
+            | Operator::I32GeS
+            | Operator::I32GeU
+            | Operator::I64Eq
+            | Operator::I64Ne
+            | Operator::I64LtS
+            | Operator::I64LtU
+            | Operator::I64GtS
+            | Operator::I64GtU
+            | Operator::I64LeS
+            | Operator::I64LeU
+            | Operator::I64GeS
+            | Operator::I64GeU
+            | Operator::F32Eq
+            | Operator::F32Ne
+            | Operator::F32Lt
+            | Operator::F32Gt
+            | Operator::F32Le
+            | Operator::F32Ge
+            | Operator::F64Eq
+            | Operator::F64Ne
+            | Operator::F64Lt
+            | Operator::F64Gt
+            | Operator::F64Le
+            | Operator::F64Ge
+            | Operator::I32Load { .. }
+            | Operator::I32Load8S { .. }
+            | Operator::I32Load8U { .. }
+            | Operator::I32Load16S { .. }
+            | Operator::I32Load16U { .. }
+            | Operator::I64Load { .. }
+            | Operator::I64Load8S { .. }
+            | Operator::I64Load8U { .. }
+            | Operator::I64Load16S { .. }
+            | Operator::I64Load16U { .. }
+            | Operator::I64Load32S { .. }
+            | Operator::I64Load32U { .. }
+            | Operator::F32Load { .. }
+            | Operator::F64Load { .. }
+            | Operator::I32Store { .. }
+            | Operator::I32Store8 { .. }
+            | Operator::I32Store16 { .. }
+            | Operator::I64Store { .. }
+            | Operator::I64Store8 { .. }
+            | Operator::I64Store16 { .. }
+            | Operator::I64Store32 { .. }
+            | Operator::F32Store { .. }
+            | Operator::F64Store { .. }
+            | Operator::MemorySize { .. }
+            | Operator::MemoryGrow { .. }
+            | Operator::Select
+            | Operator::BrIf { .. }
+            | Operator::Call { .. }
+            | Operator::CallIndirect { .. }
+            | Operator::Drop
+            | Operator::Return
+            | Operator::End
+            | Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+    )
+}
+
+/// A tiny xorshift32 PRNG. Good enough to drive module generation
+/// deterministically from a `seed` without pulling in the `rand` crate for
+/// something this small.
+struct Rng(u32);
+
+impl Rng {
+    fn new(seed: u32) -> Rng {
+        Rng(if seed == 0 { 0x9e3779b9 } else { seed })
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    /// Returns a value in `[lo, hi)`, clamping to `lo` if the range is empty.
+    fn gen_range(&mut self, lo: u32, hi: u32) -> u32 {
+        if hi <= lo {
+            return lo;
+        }
+        lo + self.next_u32() % (hi - lo)
+    }
+
+    fn gen_percent(&mut self, chance: u32) -> bool {
+        self.gen_range(0, 100) < chance
+    }
+}
+
+/// Generates a small, always-valid random module and feeds it straight
+/// into the existing `Dump` pipeline, so users can explore encodings
+/// without writing WAT by hand. `max_functions` and `max_instructions`
+/// bound the generated module's size; `seed` makes the result
+/// reproducible. `enable_reference_types` and `enable_bulk_memory` gate
+/// whether those proposals' sections/instructions (a funcref table plus
+/// `ref.func`, and `memory.fill`, respectively) may appear.
+#[wasm_bindgen]
+pub fn generate_random(
+    seed: u32,
+    max_functions: u32,
+    max_instructions: u32,
+    enable_reference_types: bool,
+    enable_bulk_memory: bool,
+) -> String {
+    let bytes = build_random_module(
+        seed,
+        max_functions.max(1),
+        max_instructions.max(1),
+        enable_reference_types,
+        enable_bulk_memory,
+    );
+
+    let mut d = Dump::new(&bytes);
+    if let Err(err) = d.run() {
+        return format!("{}", err);
+    }
+    format!(
+        "=== binary ===\n{}\n=== explain ===\n{}\n=== disasm ===\n{}",
+        d.binary, d.explain, d.disasm
+    )
+}
+
+/// Builds a handful of `(i32...) -> i32`-shaped functions, each with a
+/// stack-balanced body: a virtual operand stack is tracked while emitting
+/// instructions so every op's inputs are always available, and the body
+/// is folded back down to exactly one value before the final `end`. This
+/// is the same spirit as wasm-smith's arbitrary module generation, scaled
+/// down to what this explorer needs to exercise the
+/// `Payload::{TypeSection,FunctionSection,TableSection,MemorySection,ElementSection,CodeSectionEntry}`
+/// arms of `Dump::run_module`.
+///
+/// `enable_reference_types` adds a funcref table (with an active element
+/// segment populating it) and has function bodies occasionally emit a
+/// balanced `ref.func`/`drop` pair; `enable_bulk_memory` has them
+/// occasionally emit a `memory.fill` with synthesized operands. Both are
+/// inserted as no-ops against the i32 result stack being tracked below, so
+/// they never interfere with the "ends with exactly one i32" invariant the
+/// rest of the generator relies on.
+fn build_random_module(
+    seed: u32,
+    max_functions: u32,
+    max_instructions: u32,
+    enable_reference_types: bool,
+    enable_bulk_memory: bool,
+) -> Vec<u8> {
+    use wasm_encoder::{
+        CodeSection, ConstExpr, ElementSection, Elements, Function, FunctionSection, Instruction,
+        MemorySection, MemoryType, Module, RefType, TableSection, TableType, TypeSection, ValType,
+    };
+
+    let mut rng = Rng::new(seed);
+    let func_count = rng.gen_range(1, max_functions + 1);
+    let param_counts: Vec<u32> = (0..func_count).map(|_| rng.gen_range(0, 3)).collect();
+
+    let mut module = Module::new();
+
+    let mut types = TypeSection::new();
+    for &param_count in &param_counts {
+        types.function(
+            std::iter::repeat(ValType::I32).take(param_count as usize),
+            [ValType::I32],
+        );
+    }
+    module.section(&types);
+
+    let mut functions = FunctionSection::new();
+    for type_index in 0..func_count {
+        functions.function(type_index);
+    }
+    module.section(&functions);
+
+    if enable_reference_types {
+        let mut tables = TableSection::new();
+        tables.table(TableType {
+            element_type: RefType::FUNCREF,
+            minimum: func_count as u64,
+            maximum: None,
+            table64: false,
+            shared: false,
+        });
+        module.section(&tables);
+    }
+
+    let mut memories = MemorySection::new();
+    memories.memory(MemoryType {
+        minimum: 1,
+        maximum: None,
+        memory64: false,
+        shared: false,
+    });
+    module.section(&memories);
+
+    if enable_reference_types {
+        let mut elements = ElementSection::new();
+        let funcs: Vec<u32> = (0..func_count).collect();
+        elements.active(Some(0), &ConstExpr::i32_const(0), Elements::Functions(&funcs));
+        module.section(&elements);
+    }
+
+    let mut code = CodeSection::new();
+    for (func_index, &param_count) in param_counts.iter().enumerate() {
+        let extra_locals = rng.gen_range(0, 3);
+        let locals = if extra_locals > 0 {
+            vec![(extra_locals, ValType::I32)]
+        } else {
+            vec![]
+        };
+        let local_count = param_count + extra_locals;
+
+        let mut f = Function::new(locals);
+        let mut stack_depth: u32 = 0;
+        let instruction_count = rng.gen_range(1, max_instructions + 1);
+
+        for _ in 0..instruction_count {
+            if enable_reference_types && rng.gen_percent(10) {
+                f.instruction(&Instruction::RefFunc(func_index as u32));
+                f.instruction(&Instruction::Drop);
+            } else if enable_bulk_memory && rng.gen_percent(10) {
+                // memory.fill takes (dst, value, size): i32 i32 i32 -> (),
+                // so its operands are synthesized up front rather than
+                // drawn from `stack_depth`'s running i32 result.
+                f.instruction(&Instruction::I32Const(0));
+                f.instruction(&Instruction::I32Const(0));
+                f.instruction(&Instruction::I32Const(0));
+                f.instruction(&Instruction::MemoryFill(0));
+            } else if stack_depth >= 2 && rng.gen_percent(40) {
+                f.instruction(match rng.gen_range(0, 3) {
+                    0 => &Instruction::I32Add,
+                    1 => &Instruction::I32Sub,
+                    _ => &Instruction::I32Mul,
+                });
+                stack_depth -= 1;
+            } else if local_count > 0 && rng.gen_percent(50) {
+                f.instruction(&Instruction::LocalGet(rng.gen_range(0, local_count)));
+                stack_depth += 1;
+            } else {
+                f.instruction(&Instruction::I32Const(rng.gen_range(0, 1000) as i32));
+                stack_depth += 1;
+            }
+        }
+
+        // Fold the stack back down to exactly the one declared i32 result.
+        if stack_depth == 0 {
+            f.instruction(&Instruction::I32Const(0));
+            stack_depth = 1;
+        }
+        while stack_depth > 1 {
+            f.instruction(&Instruction::I32Add);
+            stack_depth -= 1;
+        }
+
+        f.instruction(&Instruction::End);
+        code.function(&f);
+    }
+    module.section(&code);
+
+    module.finish()
+}